@@ -1,141 +1,542 @@
+use js_sys::{Reflect, Uint8Array, JSON};
 use leptos::*;
 use serde::{Deserialize, Serialize};
-use wasm_bindgen_futures::spawn_local;
-use web_sys::{RequestInit, RequestMode};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{
+    AbortController, AbortSignal, RequestInit, RequestMode, TextDecodeOptions, TextDecoder,
+};
+
+use crate::markdown;
+use crate::storage;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-struct ChatMessage {
-    id: usize,
-    content: String,
-    is_user: bool,
+pub(crate) struct ChatMessage {
+    pub(crate) id: usize,
+    pub(crate) content: String,
+    pub(crate) is_user: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ChatHistory {
+    pub(crate) id: usize,
+    pub(crate) title: String,
+}
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const DEFAULT_MAX_TOKENS: u32 = 4096;
+const DEFAULT_MODEL: &str = "claude-3-5-sonnet-20241022";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Model {
+    id: String,
+    name: String,
+}
+
+fn available_models() -> Vec<Model> {
+    vec![
+        Model {
+            id: "claude-3-5-sonnet-20241022".to_string(),
+            name: "Claude 3.5 Sonnet".to_string(),
+        },
+        Model {
+            id: "claude-3-opus-20240229".to_string(),
+            name: "Claude 3 Opus".to_string(),
+        },
+        Model {
+            id: "claude-3-haiku-20240307".to_string(),
+            name: "Claude 3 Haiku".to_string(),
+        },
+    ]
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-struct ChatHistory {
-    id: usize,
-    title: String,
+pub(crate) struct Role {
+    name: String,
+    system_prompt: String,
+}
+
+fn builtin_roles() -> Vec<Role> {
+    vec![
+        Role {
+            name: "Shell Assistant".to_string(),
+            system_prompt: "You are a helpful shell assistant. Answer concisely with commands and brief explanations.".to_string(),
+        },
+        Role {
+            name: "Code Reviewer".to_string(),
+            system_prompt: "You are an experienced code reviewer. Point out bugs, readability issues, and possible improvements.".to_string(),
+        },
+        Role {
+            name: "Concise".to_string(),
+            system_prompt: "Answer as concisely as possible.".to_string(),
+        },
+    ]
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-struct ClaudeRequest {
-    prompt: String,
+struct Message {
+    role: String,
+    content: String,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-struct ClaudeResponse {
-    completion: String,
+struct ClaudeRequest {
+    model: String,
+    messages: Vec<Message>,
+    max_tokens: u32,
+    stream: bool,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    system: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct StreamDelta {
+    text: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct StreamEvent {
+    #[serde(rename = "type")]
+    event_type: String,
+    #[serde(default)]
+    delta: Option<StreamDelta>,
 }
 
-async fn call_claude_api(prompt: String) -> Result<String, String> {
+fn is_abort_error(err: &JsValue) -> bool {
+    Reflect::get(err, &JsValue::from_str("name"))
+        .ok()
+        .and_then(|name| name.as_string())
+        .map(|name| name == "AbortError")
+        .unwrap_or(false)
+}
+
+// Consumes the `text/event-stream` body one chunk at a time, pushing each
+// `content_block_delta`'s text into `set_streaming_message` so the UI can
+// render the reply as it arrives. Returns the fully assembled text once the
+// stream closes.
+//
+// `history` is the full conversation so far (including the message the user
+// just sent) so the model has context from earlier turns, not just the
+// latest one. `abort_signal` lets the caller cancel the request via a Stop
+// button; a resulting `AbortError` is treated as a clean finish rather than
+// a failure, returning whatever text had streamed in before the abort.
+async fn call_claude_api(
+    history: Vec<ChatMessage>,
+    model: String,
+    system_prompt: String,
+    abort_signal: AbortSignal,
+    set_streaming_message: WriteSignal<String>,
+) -> Result<String, String> {
     let window = web_sys::window().unwrap();
-    let mut opts = RequestInit::new();
-    opts.method("POST");
-    opts.mode(RequestMode::Cors);
+    let opts = RequestInit::new();
+    opts.set_method("POST");
+    opts.set_mode(RequestMode::Cors);
+    opts.set_signal(Some(&abort_signal));
 
-    let request_body = ClaudeRequest { prompt };
+    let messages = history
+        .into_iter()
+        .map(|msg| Message {
+            role: if msg.is_user { "user" } else { "assistant" }.to_string(),
+            content: msg.content,
+        })
+        .collect();
+
+    let request_body = ClaudeRequest {
+        model,
+        messages,
+        max_tokens: DEFAULT_MAX_TOKENS,
+        stream: true,
+        system: system_prompt,
+    };
     let body_str = serde_json::to_string(&request_body).map_err(|e| e.to_string())?;
-    opts.body(Some(&JSON::parse(&body_str).map_err(|e| e.to_string())?));
+    opts.set_body(&JSON::parse(&body_str).map_err(|e| format!("{:?}", e))?);
 
-    let request = web_sys::Request::new_with_str_and_init(
-        "https://api.anthropic.com/v1/completions",
-        &opts,
-    )
-    .map_err(|e| e.to_string())?;
+    let request =
+        web_sys::Request::new_with_str_and_init("https://api.anthropic.com/v1/messages", &opts)
+            .map_err(|e| format!("{:?}", e))?;
 
-    request.headers().set("Content-Type", "application/json").map_err(|e| e.to_string())?;
-    request.headers().set("X-API-Key", "YOUR_API_KEY_HERE").map_err(|e| e.to_string())?;
+    request
+        .headers()
+        .set("Content-Type", "application/json")
+        .map_err(|e| format!("{:?}", e))?;
+    request
+        .headers()
+        .set("X-API-Key", "YOUR_API_KEY_HERE")
+        .map_err(|e| format!("{:?}", e))?;
+    request
+        .headers()
+        .set("anthropic-version", ANTHROPIC_VERSION)
+        .map_err(|e| format!("{:?}", e))?;
 
-    let resp_value = JsFuture::from(window.fetch_with_request(&request))
-        .await
-        .map_err(|e| format!("fetch error: {:?}", e))?;
+    let resp_value = match JsFuture::from(window.fetch_with_request(&request)).await {
+        Ok(value) => value,
+        Err(e) if is_abort_error(&e) => return Ok(String::new()),
+        Err(e) => return Err(format!("fetch error: {:?}", e)),
+    };
 
     let resp: web_sys::Response = resp_value.dyn_into().unwrap();
 
-    let json = JsFuture::from(resp.json().map_err(|e| e.to_string())?)
-        .await
-        .map_err(|e| format!("json parse error: {:?}", e))?;
+    let body = resp
+        .body()
+        .ok_or_else(|| "response has no body".to_string())?;
+    let reader: web_sys::ReadableStreamDefaultReader = body
+        .get_reader()
+        .dyn_into()
+        .map_err(|_| "failed to acquire stream reader".to_string())?;
+
+    let decoder = TextDecoder::new().map_err(|e| format!("{:?}", e))?;
+    let decode_opts = TextDecodeOptions::new();
+    decode_opts.set_stream(true);
+    let mut buffer = String::new();
+    let mut full_text = String::new();
+
+    loop {
+        let read_result = match JsFuture::from(reader.read()).await {
+            Ok(value) => value,
+            Err(e) if is_abort_error(&e) => return Ok(full_text),
+            Err(e) => return Err(format!("stream read error: {:?}", e)),
+        };
+
+        let done = Reflect::get(&read_result, &JsValue::from_str("done"))
+            .map_err(|e| format!("{:?}", e))?
+            .as_bool()
+            .unwrap_or(false);
+
+        // Decoding in streaming mode keeps any trailing partial UTF-8
+        // sequence buffered inside `decoder` instead of emitting replacement
+        // characters when a multi-byte sequence is split across chunks. Once
+        // the stream ends, a final non-streaming decode flushes whatever is
+        // left.
+        let chunk_str = if done {
+            decoder.decode().map_err(|e| format!("{:?}", e))?
+        } else {
+            let value = Reflect::get(&read_result, &JsValue::from_str("value"))
+                .map_err(|e| format!("{:?}", e))?;
+            let chunk: Uint8Array = value
+                .dyn_into()
+                .map_err(|_| "unexpected chunk type".to_string())?;
+            decoder
+                .decode_with_buffer_source_and_options(&chunk, &decode_opts)
+                .map_err(|e| format!("{:?}", e))?
+        };
+        buffer.push_str(&chunk_str);
+
+        while let Some(event_end) = buffer.find("\n\n") {
+            let event = buffer[..event_end].to_string();
+            buffer.drain(..event_end + 2);
+
+            for line in event.lines() {
+                let Some(data) = line.strip_prefix("data: ") else {
+                    continue;
+                };
+                if data == "[DONE]" {
+                    return Ok(full_text);
+                }
+                let Ok(stream_event) = serde_json::from_str::<StreamEvent>(data) else {
+                    continue;
+                };
+                match stream_event.event_type.as_str() {
+                    "content_block_delta" => {
+                        if let Some(delta) = stream_event.delta {
+                            full_text.push_str(&delta.text);
+                            set_streaming_message.set(full_text.clone());
+                        }
+                    }
+                    "message_stop" => return Ok(full_text),
+                    _ => {}
+                }
+            }
+        }
 
-    let claude_response: ClaudeResponse = json.into_serde().map_err(|e| e.to_string())?;
+        if done {
+            break;
+        }
+    }
 
-    Ok(claude_response.completion)
+    Ok(full_text)
 }
 
 #[component]
 pub fn ChatApp() -> impl IntoView {
-    let (chat_history, _set_chat_history) = create_signal(vec![
-        ChatHistory {
+    let stored_index = storage::load_chat_index();
+    let seeded = stored_index.is_empty();
+    let initial_history = if seeded {
+        vec![ChatHistory {
             id: 1,
-            title: "Chat 1".to_string(),
-        },
-        ChatHistory {
-            id: 2,
-            title: "Chat 2".to_string(),
-        },
-        ChatHistory {
-            id: 3,
-            title: "Chat 3".to_string(),
-        },
-    ]);
+            title: "New Chat".to_string(),
+        }]
+    } else {
+        stored_index
+    };
+    if seeded {
+        storage::save_chat_index(&initial_history);
+    }
+    let initial_active_id = initial_history[0].id;
+    let initial_messages = storage::load_chat_messages(initial_active_id);
+
+    let (chat_history, set_chat_history) = create_signal(initial_history);
+    let (active_chat_id, set_active_chat_id) = create_signal(initial_active_id);
 
-    let (current_chat, set_current_chat) = create_signal(Vec::new());
+    let (current_chat, set_current_chat) = create_signal(initial_messages);
     let (input_text, set_input_text) = create_signal(String::new());
     let (is_streaming, set_is_streaming) = create_signal(false);
+    let (streaming_message, set_streaming_message) = create_signal(String::new());
+    let (abort_controller, set_abort_controller) = create_signal(None::<AbortController>);
     let (dark_mode, set_dark_mode) = create_signal(false);
     let (error_message, set_error_message) = create_signal(String::new());
 
+    // Arena mode: run the same prompt against two models side by side.
+    let (arena_mode, set_arena_mode) = create_signal(false);
+    let (models, _set_models) = create_signal(available_models());
+    let (arena_model_left, set_arena_model_left) = create_signal(DEFAULT_MODEL.to_string());
+    let (arena_model_right, set_arena_model_right) =
+        create_signal("claude-3-opus-20240229".to_string());
+    let (arena_chat_left, set_arena_chat_left) = create_signal(Vec::<ChatMessage>::new());
+    let (arena_chat_right, set_arena_chat_right) = create_signal(Vec::<ChatMessage>::new());
+    let (arena_streaming_left, set_arena_streaming_left) = create_signal(String::new());
+    let (arena_streaming_right, set_arena_streaming_right) = create_signal(String::new());
+    let (arena_vote, set_arena_vote) = create_signal(String::new());
+
+    // Role presets: steer the assistant's behavior via the Messages API's
+    // `system` parameter. Built-ins are fixed; user-defined roles persist
+    // alongside chat history.
+    let (custom_roles, set_custom_roles) = create_signal(storage::load_custom_roles());
+    let (active_role_name, set_active_role_name) = create_signal(builtin_roles()[0].name.clone());
+    let (new_role_name, set_new_role_name) = create_signal(String::new());
+    let (new_role_prompt, set_new_role_prompt) = create_signal(String::new());
+    let (show_new_role_form, set_show_new_role_form) = create_signal(false);
+
     let handle_send_message = move |ev: ev::SubmitEvent| {
         ev.prevent_default();
         let input = input_text.get();
         if input.trim().is_empty() {
             return;
         }
+        set_input_text.set(String::new());
+
+        let system_prompt = {
+            let role_name = active_role_name.get();
+            builtin_roles()
+                .into_iter()
+                .chain(custom_roles.get())
+                .find(|r| r.name == role_name)
+                .map(|r| r.system_prompt)
+                .unwrap_or_default()
+        };
+
+        if arena_mode.get() {
+            let left_user_message = ChatMessage {
+                id: arena_chat_left.with(|chat| chat.len()),
+                content: input.clone(),
+                is_user: true,
+            };
+            let right_user_message = ChatMessage {
+                id: arena_chat_right.with(|chat| chat.len()),
+                content: input,
+                is_user: true,
+            };
+            set_arena_chat_left.update(|chat| chat.push(left_user_message));
+            set_arena_chat_right.update(|chat| chat.push(right_user_message));
+            set_arena_vote.set(String::new());
+
+            let left_history = arena_chat_left.with(|chat| chat.clone());
+            let left_model = arena_model_left.get();
+            let left_signal = AbortController::new().unwrap().signal();
+            let left_system_prompt = system_prompt.clone();
+            set_arena_streaming_left.set(String::new());
+            spawn_local(async move {
+                match call_claude_api(
+                    left_history,
+                    left_model,
+                    left_system_prompt,
+                    left_signal,
+                    set_arena_streaming_left,
+                )
+                .await
+                {
+                    Ok(response) => {
+                        let new_message = ChatMessage {
+                            id: arena_chat_left.with(|chat| chat.len()),
+                            content: response,
+                            is_user: false,
+                        };
+                        set_arena_chat_left.update(|chat| chat.push(new_message));
+                    }
+                    Err(err) => set_error_message.set(format!("Error: {}", err)),
+                }
+                set_arena_streaming_left.set(String::new());
+            });
+
+            let right_history = arena_chat_right.with(|chat| chat.clone());
+            let right_model = arena_model_right.get();
+            let right_signal = AbortController::new().unwrap().signal();
+            let right_system_prompt = system_prompt.clone();
+            set_arena_streaming_right.set(String::new());
+            spawn_local(async move {
+                match call_claude_api(
+                    right_history,
+                    right_model,
+                    right_system_prompt,
+                    right_signal,
+                    set_arena_streaming_right,
+                )
+                .await
+                {
+                    Ok(response) => {
+                        let new_message = ChatMessage {
+                            id: arena_chat_right.with(|chat| chat.len()),
+                            content: response,
+                            is_user: false,
+                        };
+                        set_arena_chat_right.update(|chat| chat.push(new_message));
+                    }
+                    Err(err) => set_error_message.set(format!("Error: {}", err)),
+                }
+                set_arena_streaming_right.set(String::new());
+            });
+
+            return;
+        }
 
         let new_user_message = ChatMessage {
             id: current_chat.with(|chat| chat.len()),
-            content: input.clone(),
+            content: input,
             is_user: true,
         };
 
         set_current_chat.update(|chat| chat.push(new_user_message));
-        set_input_text.set(String::new());
+
+        let history = current_chat.with(|chat| chat.clone());
 
         // Call Claude API
+        let controller = AbortController::new().unwrap();
+        set_abort_controller.set(Some(controller.clone()));
+        let abort_signal = controller.signal();
         set_is_streaming.set(true);
+        set_streaming_message.set(String::new());
         spawn_local(async move {
-            match call_claude_api(input).await {
+            match call_claude_api(
+                history,
+                DEFAULT_MODEL.to_string(),
+                system_prompt,
+                abort_signal,
+                set_streaming_message,
+            )
+            .await
+            {
                 Ok(response) => {
-                    let new_llm_message = ChatMessage {
-                        id: current_chat.with(|chat| chat.len()),
-                        content: response,
-                        is_user: false,
-                    };
-                    set_current_chat.update(|chat| chat.push(new_llm_message));
+                    if !response.is_empty() {
+                        let new_llm_message = ChatMessage {
+                            id: current_chat.with(|chat| chat.len()),
+                            content: response,
+                            is_user: false,
+                        };
+                        set_current_chat.update(|chat| chat.push(new_llm_message));
+                    }
                     set_is_streaming.set(false);
+                    set_streaming_message.set(String::new());
                     set_error_message.set(String::new());
                 }
                 Err(err) => {
                     set_is_streaming.set(false);
+                    set_streaming_message.set(String::new());
                     set_error_message.set(format!("Error: {}", err));
                 }
             }
+            set_abort_controller.set(None);
         });
     };
 
+    let stop_generation = move |_| {
+        if let Some(controller) = abort_controller.get() {
+            controller.abort();
+        }
+    };
+
+    let toggle_arena_mode = move |_| set_arena_mode.update(|am| *am = !*am);
+    let vote_left = move |_| set_arena_vote.set("left".to_string());
+    let vote_right = move |_| set_arena_vote.set("right".to_string());
+    let vote_tie = move |_| set_arena_vote.set("tie".to_string());
+
     let toggle_dark_mode = move |_| set_dark_mode.update(|dm| *dm = !*dm);
 
+    let toggle_new_role_form = move |_| set_show_new_role_form.update(|s| *s = !*s);
+    let save_new_role = move |_| {
+        let name = new_role_name.get();
+        if name.trim().is_empty() {
+            return;
+        }
+        let role = Role {
+            name: name.clone(),
+            system_prompt: new_role_prompt.get(),
+        };
+        set_custom_roles.update(|roles| roles.push(role));
+        storage::save_custom_roles(&custom_roles.get());
+        set_active_role_name.set(name);
+        set_new_role_name.set(String::new());
+        set_new_role_prompt.set(String::new());
+        set_show_new_role_form.set(false);
+    };
+
+    let select_chat = move |id: usize| {
+        batch(move || {
+            set_current_chat.set(storage::load_chat_messages(id));
+            set_active_chat_id.set(id);
+        });
+    };
+
+    let new_chat = move |_| {
+        let new_id =
+            chat_history.with(|history| history.iter().map(|c| c.id).max().unwrap_or(0) + 1);
+        let new_entry = ChatHistory {
+            id: new_id,
+            title: format!("Chat {}", new_id),
+        };
+        set_chat_history.update(|history| history.push(new_entry));
+        storage::save_chat_index(&chat_history.get());
+        batch(move || {
+            set_current_chat.set(Vec::new());
+            set_active_chat_id.set(new_id);
+        });
+    };
+
+    // Auto-save the active chat's messages to localStorage on every change.
+    create_effect(move |_| {
+        let messages = current_chat.get();
+        let id = active_chat_id.get();
+        if let Some(history_entry) =
+            chat_history.with(|history| history.iter().find(|c| c.id == id).cloned())
+        {
+            storage::save_chat_messages(&history_entry, &messages);
+        }
+    });
+
     view! {
         <div class=move || format!("flex h-screen {}", if dark_mode.get() { "bg-gray-900 text-white" } else { "bg-gray-100" })>
             // Left Sidebar
             <div class=move || format!("w-64 {} p-4", if dark_mode.get() { "bg-gray-800 text-white" } else { "bg-gray-200 text-black" })>
                 <h2 class="text-xl font-bold mb-4">"Chat History"</h2>
                 <ul>
-                    {move || chat_history.get().into_iter().map(|chat| view! {
-                        <li key={chat.id} class=move || format!("mb-2 cursor-pointer {} p-2 rounded",
-                            if dark_mode.get() { "hover:bg-gray-700" } else { "hover:bg-gray-300" })>
-                            {chat.title}
-                        </li>
+                    {move || chat_history.get().into_iter().map(|chat| {
+                        let chat_id = chat.id;
+                        view! {
+                            <li key={chat.id}
+                                class=move || format!("mb-2 cursor-pointer {} p-2 rounded {}",
+                                    if dark_mode.get() { "hover:bg-gray-700" } else { "hover:bg-gray-300" },
+                                    if active_chat_id.get() == chat_id { "font-bold" } else { "" })
+                                on:click=move |_| select_chat(chat_id)
+                            >
+                                {chat.title}
+                            </li>
+                        }
                     }).collect::<Vec<_>>()}
                 </ul>
+                <button
+                    class="mb-2 px-4 py-2 rounded w-full bg-blue-500 text-white hover:bg-blue-600"
+                    on:click=new_chat
+                >
+                    "New Chat"
+                </button>
                 <button
                     class=move || format!("mt-4 px-4 py-2 rounded {}",
                         if dark_mode.get() { "bg-gray-600 hover:bg-gray-500" } else { "bg-gray-300 hover:bg-gray-400" })
@@ -143,44 +544,220 @@ pub fn ChatApp() -> impl IntoView {
                 >
                     {move || if dark_mode.get() { "Light Mode" } else { "Dark Mode" }}
                 </button>
+                <button
+                    class=move || format!("mt-2 px-4 py-2 rounded w-full {}",
+                        if arena_mode.get() {
+                            "bg-purple-600 text-white hover:bg-purple-500"
+                        } else if dark_mode.get() {
+                            "bg-gray-600 hover:bg-gray-500"
+                        } else {
+                            "bg-gray-300 hover:bg-gray-400"
+                        })
+                    on:click=toggle_arena_mode
+                >
+                    {move || if arena_mode.get() { "Exit Arena Mode" } else { "Arena Mode" }}
+                </button>
+                {move || arena_mode.get().then(|| view! {
+                    <div class="mt-2">
+                        <label class="block text-xs mb-1">"Left model"</label>
+                        <select
+                            class="w-full mb-2 p-1 rounded text-black"
+                            on:change=move |ev| set_arena_model_left.set(event_target_value(&ev))
+                        >
+                            {move || models.get().into_iter().map(|m| view! {
+                                <option value={m.id.clone()} selected={m.id == arena_model_left.get()}>
+                                    {m.name}
+                                </option>
+                            }).collect::<Vec<_>>()}
+                        </select>
+                        <label class="block text-xs mb-1">"Right model"</label>
+                        <select
+                            class="w-full p-1 rounded text-black"
+                            on:change=move |ev| set_arena_model_right.set(event_target_value(&ev))
+                        >
+                            {move || models.get().into_iter().map(|m| view! {
+                                <option value={m.id.clone()} selected={m.id == arena_model_right.get()}>
+                                    {m.name}
+                                </option>
+                            }).collect::<Vec<_>>()}
+                        </select>
+                    </div>
+                })}
+                <div class="mt-4">
+                    <label class="block text-xs mb-1">"Role"</label>
+                    <select
+                        class="w-full p-1 rounded text-black"
+                        on:change=move |ev| set_active_role_name.set(event_target_value(&ev))
+                    >
+                        {move || builtin_roles().into_iter().chain(custom_roles.get()).map(|r| view! {
+                            <option value={r.name.clone()} selected={r.name == active_role_name.get()}>
+                                {r.name}
+                            </option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                    <button
+                        class="mt-1 text-xs underline"
+                        on:click=toggle_new_role_form
+                    >
+                        "+ New role"
+                    </button>
+                    {move || show_new_role_form.get().then(|| view! {
+                        <div class="mt-1">
+                            <input
+                                class="w-full mb-1 p-1 rounded text-black"
+                                placeholder="Role name"
+                                prop:value=move || new_role_name.get()
+                                on:input=move |ev| set_new_role_name.set(event_target_value(&ev))
+                            />
+                            <textarea
+                                class="w-full mb-1 p-1 rounded text-black"
+                                placeholder="System prompt"
+                                prop:value=move || new_role_prompt.get()
+                                on:input=move |ev| set_new_role_prompt.set(event_target_value(&ev))
+                            />
+                            <button
+                                class="px-2 py-1 rounded w-full bg-blue-500 text-white hover:bg-blue-600"
+                                on:click=save_new_role
+                            >
+                                "Save role"
+                            </button>
+                        </div>
+                    })}
+                </div>
             </div>
 
             // Main Chat Area
             <div class="flex-1 flex flex-col">
-                // Chat Messages
-                <div class="flex-1 p-4 overflow-y-auto">
-                    {move || current_chat.get().into_iter().map(|message| view! {
-                        <div class={format!("mb-4 {}", if message.is_user { "text-right" } else { "text-left" })}>
-                            <div class=move || format!("inline-block p-2 rounded-lg {}",
-                                if message.is_user {
-                                    if dark_mode.get() { "bg-blue-600 text-white" } else { "bg-blue-500 text-white" }
-                                } else {
-                                    if dark_mode.get() { "bg-gray-700 text-white" } else { "bg-gray-300 text-black" }
-                                })>
-                                {message.content}
+                {move || if arena_mode.get() {
+                    view! {
+                        <div class="flex-1 flex overflow-hidden">
+                            <div class="flex-1 p-4 overflow-y-auto border-r border-gray-500">
+                                {move || arena_chat_left.get().into_iter().map(|message| view! {
+                                    <div class={format!("mb-4 {}", if message.is_user { "text-right" } else { "text-left" })}>
+                                        <div class=move || format!("inline-block p-2 rounded-lg {}",
+                                            if message.is_user {
+                                                if dark_mode.get() { "bg-blue-600 text-white" } else { "bg-blue-500 text-white" }
+                                            } else {
+                                                if dark_mode.get() { "bg-gray-700 text-white" } else { "bg-gray-300 text-black" }
+                                            })>
+                                            {if message.is_user {
+                                                view! { <span>{message.content}</span> }
+                                            } else {
+                                                view! { <span inner_html=markdown::render(&message.content)></span> }
+                                            }}
+                                        </div>
+                                    </div>
+                                }).collect::<Vec<_>>()}
+                                {move || {
+                                    let partial = arena_streaming_left.get();
+                                    (!partial.is_empty()).then(|| view! {
+                                        <div class="text-left">
+                                            <div class=move || format!("inline-block p-2 rounded-lg {}",
+                                                if dark_mode.get() { "bg-gray-700" } else { "bg-gray-300" })>
+                                                {partial}
+                                            </div>
+                                        </div>
+                                    })
+                                }}
+                            </div>
+                            <div class="flex-1 p-4 overflow-y-auto">
+                                {move || arena_chat_right.get().into_iter().map(|message| view! {
+                                    <div class={format!("mb-4 {}", if message.is_user { "text-right" } else { "text-left" })}>
+                                        <div class=move || format!("inline-block p-2 rounded-lg {}",
+                                            if message.is_user {
+                                                if dark_mode.get() { "bg-blue-600 text-white" } else { "bg-blue-500 text-white" }
+                                            } else {
+                                                if dark_mode.get() { "bg-gray-700 text-white" } else { "bg-gray-300 text-black" }
+                                            })>
+                                            {if message.is_user {
+                                                view! { <span>{message.content}</span> }
+                                            } else {
+                                                view! { <span inner_html=markdown::render(&message.content)></span> }
+                                            }}
+                                        </div>
+                                    </div>
+                                }).collect::<Vec<_>>()}
+                                {move || {
+                                    let partial = arena_streaming_right.get();
+                                    (!partial.is_empty()).then(|| view! {
+                                        <div class="text-left">
+                                            <div class=move || format!("inline-block p-2 rounded-lg {}",
+                                                if dark_mode.get() { "bg-gray-700" } else { "bg-gray-300" })>
+                                                {partial}
+                                            </div>
+                                        </div>
+                                    })
+                                }}
                             </div>
                         </div>
-                    }).collect::<Vec<_>>()}
-                    {move || is_streaming.get().then(|| view! {
-                        <div class="text-left">
-                            <div class=move || format!("inline-block p-2 rounded-lg {}",
-                                if dark_mode.get() { "bg-gray-700" } else { "bg-gray-300" })>
-                                <span class="animate-pulse">"..."</span>
+                    }
+                } else {
+                    view! {
+                        <div class="flex-1 flex">
+                            <div class="flex-1 p-4 overflow-y-auto">
+                                {move || current_chat.get().into_iter().map(|message| view! {
+                                    <div class={format!("mb-4 {}", if message.is_user { "text-right" } else { "text-left" })}>
+                                        <div class=move || format!("inline-block p-2 rounded-lg {}",
+                                            if message.is_user {
+                                                if dark_mode.get() { "bg-blue-600 text-white" } else { "bg-blue-500 text-white" }
+                                            } else {
+                                                if dark_mode.get() { "bg-gray-700 text-white" } else { "bg-gray-300 text-black" }
+                                            })>
+                                            {if message.is_user {
+                                                view! { <span>{message.content}</span> }
+                                            } else {
+                                                view! { <span inner_html=markdown::render(&message.content)></span> }
+                                            }}
+                                        </div>
+                                    </div>
+                                }).collect::<Vec<_>>()}
+                                {move || is_streaming.get().then(|| view! {
+                                    <div class="text-left">
+                                        <div class=move || format!("inline-block p-2 rounded-lg {}",
+                                            if dark_mode.get() { "bg-gray-700" } else { "bg-gray-300" })>
+                                            {move || {
+                                                let partial = streaming_message.get();
+                                                if partial.is_empty() {
+                                                    view! { <span class="animate-pulse">"..."</span> }
+                                                } else {
+                                                    view! { <span>{partial}</span> }
+                                                }
+                                            }}
+                                        </div>
+                                    </div>
+                                })}
                             </div>
                         </div>
-                    })}
-                    {move || {
-                        let error = error_message.get();
-                        if !error.is_empty() {
-                            view! {
-                                <div class="text-red-500 mt-2">
-                                    {error}
-                                </div>
-                            }
-                        } else {
-                            view! { <div></div> }
+                    }
+                }}
+                {move || arena_mode.get().then(|| view! {
+                    <div class="flex justify-center gap-4 p-2 border-t border-gray-500">
+                        <button class="px-3 py-1 rounded bg-blue-500 text-white hover:bg-blue-600" on:click=vote_left>"Prefer Left"</button>
+                        <button class="px-3 py-1 rounded bg-gray-500 text-white hover:bg-gray-600" on:click=vote_tie>"Tie"</button>
+                        <button class="px-3 py-1 rounded bg-blue-500 text-white hover:bg-blue-600" on:click=vote_right>"Prefer Right"</button>
+                        {move || {
+                            let vote = arena_vote.get();
+                            (!vote.is_empty()).then(|| view! {
+                                <span class="self-center text-sm">{format!("Recorded: {}", vote)}</span>
+                            })
+                        }}
+                    </div>
+                })}
+                {move || {
+                    let error = error_message.get();
+                    if !error.is_empty() {
+                        view! {
+                            <div class="text-red-500 mt-2 px-4">
+                                {error}
+                            </div>
                         }
-                    }}
+                    } else {
+                        view! { <div></div> }
+                    }
+                }}
+
+                <div class="px-4 pt-2 text-xs opacity-75">
+                    {move || format!("Role: {}", active_role_name.get())}
                 </div>
 
                 // Input Area
@@ -194,10 +771,11 @@ pub fn ChatApp() -> impl IntoView {
                             on:input=move |ev| set_input_text.set(event_target_value(&ev))
                         />
                         <button
-                            type="submit"
+                            type=move || if is_streaming.get() { "button" } else { "submit" }
                             class="bg-blue-500 text-white p-2 rounded-r-lg hover:bg-blue-600 focus:outline-none focus:ring-2 focus:ring-blue-500"
+                            on:click=stop_generation
                         >
-                            "Send"
+                            {move || if is_streaming.get() { "Stop" } else { "Send" }}
                         </button>
                     </form>
                 </div>
@@ -1,4 +1,6 @@
 mod chat;
+mod markdown;
+mod storage;
 
 use chat::*;
 use leptos::*;
@@ -0,0 +1,19 @@
+use pulldown_cmark::{html, Options};
+
+// Renders assistant replies (which are frequently Markdown with fenced code)
+// to sanitized HTML for `inner_html`. User messages are never passed through
+// here and stay plain text.
+pub(crate) fn render(content: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+
+    let parser = pulldown_cmark::Parser::new_ext(content, options);
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, parser);
+
+    ammonia::Builder::default()
+        .add_generic_attributes(&["class"])
+        .clean(&unsafe_html)
+        .to_string()
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use web_sys::Storage;
+
+use crate::chat::{ChatHistory, ChatMessage, Role};
+
+// Bump this whenever `StoredChat`'s shape changes so old entries left over
+// from a previous schema are discarded instead of failing to deserialize.
+const SCHEMA_VERSION: u32 = 1;
+const INDEX_KEY: &str = "heycast.chat_index";
+const ROLES_KEY: &str = "heycast.custom_roles";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct StoredChat {
+    schema_version: u32,
+    history: ChatHistory,
+    messages: Vec<ChatMessage>,
+}
+
+fn chat_key(id: usize) -> String {
+    format!("heycast.chat.{}", id)
+}
+
+fn local_storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+pub(crate) fn load_chat_index() -> Vec<ChatHistory> {
+    let Some(storage) = local_storage() else {
+        return Vec::new();
+    };
+    let Ok(Some(raw)) = storage.get_item(INDEX_KEY) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&raw).unwrap_or_default()
+}
+
+pub(crate) fn save_chat_index(history: &[ChatHistory]) {
+    let Some(storage) = local_storage() else {
+        return;
+    };
+    if let Ok(raw) = serde_json::to_string(history) {
+        let _ = storage.set_item(INDEX_KEY, &raw);
+    }
+}
+
+pub(crate) fn load_chat_messages(id: usize) -> Vec<ChatMessage> {
+    let Some(storage) = local_storage() else {
+        return Vec::new();
+    };
+    let Ok(Some(raw)) = storage.get_item(&chat_key(id)) else {
+        return Vec::new();
+    };
+    let Ok(stored) = serde_json::from_str::<StoredChat>(&raw) else {
+        return Vec::new();
+    };
+    if stored.schema_version != SCHEMA_VERSION {
+        return Vec::new();
+    }
+    stored.messages
+}
+
+pub(crate) fn save_chat_messages(history: &ChatHistory, messages: &[ChatMessage]) {
+    let Some(storage) = local_storage() else {
+        return;
+    };
+    let stored = StoredChat {
+        schema_version: SCHEMA_VERSION,
+        history: history.clone(),
+        messages: messages.to_vec(),
+    };
+    if let Ok(raw) = serde_json::to_string(&stored) {
+        let _ = storage.set_item(&chat_key(history.id), &raw);
+    }
+}
+
+pub(crate) fn load_custom_roles() -> Vec<Role> {
+    let Some(storage) = local_storage() else {
+        return Vec::new();
+    };
+    let Ok(Some(raw)) = storage.get_item(ROLES_KEY) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&raw).unwrap_or_default()
+}
+
+pub(crate) fn save_custom_roles(roles: &[Role]) {
+    let Some(storage) = local_storage() else {
+        return;
+    };
+    if let Ok(raw) = serde_json::to_string(roles) {
+        let _ = storage.set_item(ROLES_KEY, &raw);
+    }
+}